@@ -0,0 +1,87 @@
+use futures::{
+    io::{AsyncRead, AsyncWrite},
+    task::LocalWaker,
+    Poll,
+};
+use std::cell::Cell;
+use std::io::{self, Read, Write};
+
+use crate::REACTOR;
+
+/// Generic async wrapper around any `mio::Evented` source.
+///
+/// Registers the wrapped source with `REACTOR` on construction and drives
+/// reads/writes through the usual `WouldBlock` -> `reregister` dance, so a
+/// new socket type only has to implement `mio::Evented` (and `Read`/`Write`
+/// to get `AsyncRead`/`AsyncWrite` for free) instead of repeating this logic.
+#[derive(Debug)]
+pub struct Async<T> {
+    io: T,
+    /// The `wakers` slab token this source is currently registered under;
+    /// tracked so `poll_with` can free the previous slot on `reregister`
+    /// instead of leaking one per `WouldBlock`.
+    token: Cell<usize>,
+}
+
+impl<T: mio::Evented> Async<T> {
+    /// Wrap `io`, registering it with `REACTOR`.
+    pub fn new(io: T) -> Result<Async<T>, io::Error> {
+        let token = REACTOR.with(|handle| handle.register(&io))?;
+        Ok(Async { io, token: Cell::new(token) })
+    }
+
+    /// Get a reference to the wrapped source.
+    pub fn get_ref(&self) -> &T {
+        &self.io
+    }
+
+    /// Get a mutable reference to the wrapped source.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.io
+    }
+
+    /// Run `op` against the wrapped source, translating `WouldBlock` into a
+    /// `reregister` for `interest` plus `Poll::Pending`.
+    pub fn poll_with<R>(
+        &mut self,
+        waker: &LocalWaker,
+        interest: mio::Ready,
+        mut op: impl FnMut(&mut T) -> io::Result<R>,
+    ) -> Poll<Result<R, io::Error>> {
+        match op(&mut self.io) {
+            Ok(result) => Poll::Ready(Ok(result)),
+            Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => {
+                let previous = self.token.get();
+                match REACTOR.with(|handle| handle.reregister(&self.io, previous, waker.clone(), interest)) {
+                    Ok(token) => {
+                        self.token.set(token);
+                        Poll::Pending
+                    },
+                    Err(err) => Poll::Ready(Err(err)),
+                }
+            },
+            Err(err) => Poll::Ready(Err(err)),
+        }
+    }
+}
+
+impl<T: Read + Write + mio::Evented> AsyncRead for Async<T> {
+    fn poll_read(&mut self, waker: &LocalWaker, buf: &mut [u8]) -> Poll<Result<usize, io::Error>> {
+        self.poll_with(waker, mio::Ready::readable(), |io| io.read(buf))
+    }
+}
+
+impl<T: Read + Write + mio::Evented> AsyncWrite for Async<T> {
+    fn poll_write(&mut self, waker: &LocalWaker, buf: &[u8]) -> Poll<Result<usize, io::Error>> {
+        self.poll_with(waker, mio::Ready::writable(), |io| io.write(buf))
+    }
+
+    fn poll_flush(&mut self, _waker: &LocalWaker) -> Poll<Result<(), io::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(&mut self, _waker: &LocalWaker) -> Poll<Result<(), io::Error>> {
+        // Socket will be closed when this Async was dropped
+        Poll::Ready(Ok(()))
+    }
+}
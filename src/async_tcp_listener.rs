@@ -3,13 +3,17 @@ use futures::stream::Stream;
 use futures::task::LocalWaker;
 use futures::task::Poll;
 use mio::net::TcpListener;
+use std::cell::Cell;
 use std::io;
 use std::net::SocketAddr;
 use std::pin::Pin;
 
 /// AsyncTcpListener is a wrapper of mio::net::TcpListener
 #[derive(Debug)]
-pub struct AsyncTcpListener(TcpListener);
+pub struct AsyncTcpListener {
+    listener: TcpListener,
+    token: Cell<usize>,
+}
 
 impl AsyncTcpListener {
     /// Bind to the address and start listening
@@ -20,28 +24,38 @@ impl AsyncTcpListener {
     /// Convert `TcpListener` to `AsyncTcpListener`, register this
     /// `AsyncTcpListener` to `REACTOR`
     pub fn from_tcp_listener(listener: TcpListener) -> Result<AsyncTcpListener, io::Error> {
-        REACTOR.with(|handle| handle.register(&listener))?;
-        Ok(AsyncTcpListener(listener))
+        let token = REACTOR.with(|handle| handle.register(&listener))?;
+        Ok(AsyncTcpListener { listener, token: Cell::new(token) })
     }
 
     /// Get all incoming stream as a `Stream`
     pub fn incoming(self) -> Incoming {
-        Incoming(self.0)
+        Incoming { listener: self.listener, token: self.token }
     }
 }
 
 /// A `Stream` for accepting connection from `AsyncTcpListener`
-pub struct Incoming(TcpListener);
+#[derive(Debug)]
+pub struct Incoming {
+    listener: TcpListener,
+    /// The `wakers` slab token this listener is currently registered under;
+    /// freed on the next `reregister` instead of being leaked.
+    token: Cell<usize>,
+}
 
 impl Stream for Incoming {
     type Item = Result<AsyncTcpStream, io::Error>;
 
     fn poll_next(self: Pin<&mut Self>, waker: &LocalWaker) -> Poll<Option<Self::Item>> {
-        match self.0.accept() {
+        match self.listener.accept() {
             Ok((stream, _)) => Poll::Ready(Some(AsyncTcpStream::from_tcp_stream(stream))),
             Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => {
-                match REACTOR.with(|reactor| reactor.reregister(&self.0, waker.clone(), mio::Ready::readable())) {
-                    Ok(_) => Poll::Pending,
+                let previous = self.token.get();
+                match REACTOR.with(|reactor| reactor.reregister(&self.listener, previous, waker.clone(), mio::Ready::readable())) {
+                    Ok(token) => {
+                        self.token.set(token);
+                        Poll::Pending
+                    },
                     Err(err) => Poll::Ready(Some(Err(err))),
                 }
             },
@@ -0,0 +1,62 @@
+use futures::{future::poll_fn, Future};
+use mio::net::UdpSocket;
+use std::{io, net::SocketAddr};
+
+use crate::Async;
+
+/// `AsyncUdpSocket` is a wrapper of `mio::net::UdpSocket`
+#[derive(Debug)]
+pub struct AsyncUdpSocket(Async<UdpSocket>);
+
+impl AsyncUdpSocket {
+    /// Bind to the address.
+    pub fn bind(addr: &SocketAddr) -> Result<AsyncUdpSocket, io::Error> {
+        UdpSocket::bind(addr).and_then(AsyncUdpSocket::from_udp_socket)
+    }
+
+    /// Convert `UdpSocket` to `AsyncUdpSocket`, register this `AsyncUdpSocket`
+    /// to `REACTOR`
+    pub fn from_udp_socket(socket: UdpSocket) -> Result<AsyncUdpSocket, io::Error> {
+        Ok(AsyncUdpSocket(Async::new(socket)?))
+    }
+
+    /// Connect the socket to a remote address, so `send`/`recv` can be used
+    /// instead of `send_to`/`recv_from`.
+    pub fn connect(&self, addr: &SocketAddr) -> Result<(), io::Error> {
+        self.0.get_ref().connect(*addr)
+    }
+
+    /// Send `buf` to `addr`.
+    pub fn send_to<'a>(
+        &'a mut self,
+        buf: &'a [u8],
+        addr: &'a SocketAddr,
+    ) -> impl Future<Output = Result<usize, io::Error>> + 'a {
+        poll_fn(move |waker| {
+            self.0
+                .poll_with(waker, mio::Ready::writable(), |socket| socket.send_to(buf, addr))
+        })
+    }
+
+    /// Receive a datagram into `buf`, returning the number of bytes read and
+    /// the address it was sent from.
+    pub fn recv_from<'a>(
+        &'a mut self,
+        buf: &'a mut [u8],
+    ) -> impl Future<Output = Result<(usize, SocketAddr), io::Error>> + 'a {
+        poll_fn(move |waker| {
+            self.0
+                .poll_with(waker, mio::Ready::readable(), |socket| socket.recv_from(buf))
+        })
+    }
+
+    /// Send `buf` to the connected remote address.
+    pub fn send<'a>(&'a mut self, buf: &'a [u8]) -> impl Future<Output = Result<usize, io::Error>> + 'a {
+        poll_fn(move |waker| self.0.poll_with(waker, mio::Ready::writable(), |socket| socket.send(buf)))
+    }
+
+    /// Receive a datagram from the connected remote address into `buf`.
+    pub fn recv<'a>(&'a mut self, buf: &'a mut [u8]) -> impl Future<Output = Result<usize, io::Error>> + 'a {
+        poll_fn(move |waker| self.0.poll_with(waker, mio::Ready::readable(), |socket| socket.recv(buf)))
+    }
+}
@@ -0,0 +1,65 @@
+use crate::{AsyncUnixStream, REACTOR};
+use futures::stream::Stream;
+use futures::task::LocalWaker;
+use futures::task::Poll;
+use mio_uds::UnixListener;
+use std::cell::Cell;
+use std::io;
+use std::path::Path;
+use std::pin::Pin;
+
+/// AsyncUnixListener is a wrapper of mio_uds::UnixListener
+#[derive(Debug)]
+pub struct AsyncUnixListener {
+    listener: UnixListener,
+    token: Cell<usize>,
+}
+
+impl AsyncUnixListener {
+    /// Bind to the path and start listening
+    pub fn bind<P: AsRef<Path>>(path: P) -> Result<AsyncUnixListener, io::Error> {
+        UnixListener::bind(path).and_then(AsyncUnixListener::from_unix_listener)
+    }
+
+    /// Convert `UnixListener` to `AsyncUnixListener`, register this
+    /// `AsyncUnixListener` to `REACTOR`
+    pub fn from_unix_listener(listener: UnixListener) -> Result<AsyncUnixListener, io::Error> {
+        let token = REACTOR.with(|handle| handle.register(&listener))?;
+        Ok(AsyncUnixListener { listener, token: Cell::new(token) })
+    }
+
+    /// Get all incoming stream as a `Stream`
+    pub fn incoming(self) -> Incoming {
+        Incoming { listener: self.listener, token: self.token }
+    }
+}
+
+/// A `Stream` for accepting connection from `AsyncUnixListener`
+#[derive(Debug)]
+pub struct Incoming {
+    listener: UnixListener,
+    /// The `wakers` slab token this listener is currently registered under;
+    /// freed on the next `reregister` instead of being leaked.
+    token: Cell<usize>,
+}
+
+impl Stream for Incoming {
+    type Item = Result<AsyncUnixStream, io::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, waker: &LocalWaker) -> Poll<Option<Self::Item>> {
+        match self.listener.accept() {
+            Ok(Some((stream, _))) => Poll::Ready(Some(AsyncUnixStream::from_unix_stream(stream))),
+            Ok(None) => {
+                let previous = self.token.get();
+                match REACTOR.with(|reactor| reactor.reregister(&self.listener, previous, waker.clone(), mio::Ready::readable())) {
+                    Ok(token) => {
+                        self.token.set(token);
+                        Poll::Pending
+                    },
+                    Err(err) => Poll::Ready(Some(Err(err))),
+                }
+            },
+            Err(err) => Poll::Ready(Some(Err(err))),
+        }
+    }
+}
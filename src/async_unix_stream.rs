@@ -0,0 +1,25 @@
+use mio_uds::UnixStream;
+use std::{io, path::Path};
+
+use crate::Async;
+
+/// `AsyncUnixStream` is a wrapper of `mio_uds::UnixStream`
+pub type AsyncUnixStream = Async<UnixStream>;
+
+impl AsyncUnixStream {
+    /// Connect to the Unix domain socket at `path`.
+    ///
+    /// Mirrors `AsyncTcpStream::connect`: the connect itself has no need to
+    /// be a future, `mio_uds::UnixStream` performs the connect
+    /// asynchronously and reads/writes will become pending until it
+    /// completes.
+    pub fn connect<P: AsRef<Path>>(path: P) -> Result<AsyncUnixStream, io::Error> {
+        UnixStream::connect(path).and_then(AsyncUnixStream::from_unix_stream)
+    }
+
+    /// Convert `UnixStream` to `AsyncUnixStream`, register this
+    /// `AsyncUnixStream` to `REACTOR`
+    pub fn from_unix_stream(stream: UnixStream) -> Result<AsyncUnixStream, io::Error> {
+        Async::new(stream)
+    }
+}
@@ -0,0 +1,65 @@
+use futures::{task::LocalWaker, Future, Poll};
+use std::{
+    pin::Pin,
+    time::{Duration, Instant},
+};
+
+use crate::REACTOR;
+
+/// A future that completes once a deadline has elapsed.
+#[derive(Debug)]
+pub struct Delay {
+    deadline: Instant,
+    id: Option<usize>,
+}
+
+impl Delay {
+    /// Create a new `Delay` that fires after `duration`.
+    pub fn new(duration: Duration) -> Delay {
+        Delay {
+            deadline: Instant::now() + duration,
+            id: None,
+        }
+    }
+}
+
+impl Drop for Delay {
+    /// Cancel the armed timer, if any, so a `Delay` dropped before firing
+    /// (the common case when it loses a `select`/timeout race) doesn't keep
+    /// the reactor counter elevated and stall `start_loop` until the
+    /// original deadline anyway.
+    ///
+    /// Uses `try_with` rather than `with`: a `Delay` can be dropped from
+    /// inside `REACTOR`'s own thread_local destructor (e.g. it's still
+    /// owned by a future sitting in the `futures` slab when the thread
+    /// exits), and `with` panics if the key is already being torn down.
+    /// There's nothing to clean up in that case anyway since the reactor
+    /// itself is going away.
+    fn drop(&mut self) {
+        if let Some(id) = self.id {
+            let _ = REACTOR.try_with(|handle| handle.cancel_timer(self.deadline, id));
+        }
+    }
+}
+
+impl Future for Delay {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, waker: &LocalWaker) -> Poll<()> {
+        let this = Pin::into_inner(self);
+        if Instant::now() >= this.deadline {
+            return Poll::Ready(());
+        }
+        if this.id.is_none() {
+            let deadline = this.deadline;
+            let id = REACTOR.with(|handle| handle.register_timer(deadline, waker.clone()));
+            this.id = Some(id);
+        }
+        Poll::Pending
+    }
+}
+
+/// Create a future that completes after `duration` has elapsed.
+pub fn sleep(duration: Duration) -> Delay {
+    Delay::new(duration)
+}
@@ -1,21 +1,39 @@
 #![feature(futures_api, pin, async_await, await_macro, arbitrary_self_types)]
 
-use std::{io, ops::Deref, sync::Arc, rc::Rc, cell::RefCell, mem::PinMut, thread_local};
+use std::{
+    collections::BTreeMap, io, ops::Deref, rc::Rc, cell::RefCell, mem::PinMut,
+    thread_local, time::{Duration, Instant},
+    sync::{Arc, atomic::{AtomicUsize, Ordering}},
+};
 use futures::{
     Poll,
     future::{Future, FutureObj},
     task::{Context, Spawn, SpawnObjError, Wake, LocalWaker, local_waker}
 };
 use log::{debug, trace};
-
-mod leak_storage;
-use crate::leak_storage::LeakStorage;
+use slab::Slab;
 
 // Re-export modules exports
+mod async_io;
+pub use crate::async_io::Async;
 mod async_tcp_stream;
 pub use crate::async_tcp_stream::AsyncTcpStream;
 mod async_tcp_listener;
 pub use crate::async_tcp_listener::AsyncTcpListener;
+mod async_udp_socket;
+pub use crate::async_udp_socket::AsyncUdpSocket;
+mod delay;
+pub use crate::delay::{sleep, Delay};
+mod threaded;
+pub use crate::threaded::run_threaded;
+#[cfg(unix)]
+mod async_unix_stream;
+#[cfg(unix)]
+pub use crate::async_unix_stream::AsyncUnixStream;
+#[cfg(unix)]
+mod async_unix_listener;
+#[cfg(unix)]
+pub use crate::async_unix_listener::AsyncUnixListener;
 
 thread_local! {
     /// The global reactor.
@@ -27,7 +45,11 @@ thread_local! {
 pub struct Reactor {
     poll: mio::Poll,
     events: RefCell<mio::Events>,
-    counter: RefCell<usize>,
+    counter: AtomicUsize,
+    timers: RefCell<BTreeMap<(Instant, usize), LocalWaker>>,
+    timer_ids: RefCell<usize>,
+    futures: RefCell<Slab<Rc<RefCell<FutureObj<'static, ()>>>>>,
+    wakers: RefCell<Slab<LocalWaker>>,
 }
 
 /// Handle of `Reactor`.
@@ -43,14 +65,33 @@ impl Deref for Handle {
     }
 }
 
+/// A waker that does nothing when woken.
+///
+/// Used to reserve a real `wakers` slab slot at `register` time, before any
+/// real waker exists yet (the source has `Ready::empty()` interest then, so
+/// this waker is never actually invoked).
+struct NoopWaker;
+
+impl Wake for NoopWaker {
+    fn wake(_arc_self: &Arc<NoopWaker>) {}
+}
+
+fn noop_waker() -> LocalWaker {
+    unsafe { local_waker(Arc::new(NoopWaker)) }
+}
+
 /// The struct inside futures' waker.
 #[derive(Debug, Clone, Copy)]
 struct InnerWaker(usize);
 
 impl InnerWaker {
-    /// Store `FutureObj` to `REACTOR`'s `future_storage`.
+    /// Store `FutureObj` in `REACTOR`'s `futures` slab, returning a waker
+    /// that refers to it by slab index.
     fn new(future: FutureObj<'static, ()>) -> InnerWaker {
-        InnerWaker(LeakStorage::insert(future))
+        let key = REACTOR.with(|handle| {
+            handle.futures.borrow_mut().insert(Rc::new(RefCell::new(future)))
+        });
+        InnerWaker(key)
     }
 
     /// Crate a local_waker from InnerWaker.
@@ -65,11 +106,15 @@ impl Wake for InnerWaker {
         let mut handle = Reactor::handle();
         let waker = unsafe { local_waker(arc_self.clone()) };
         let mut context = Context::new(&waker, &mut handle);
-        let future = PinMut::new(unsafe { LeakStorage::<FutureObj<'static, ()>>::get_ref_mut(arc_self.0) });
-        match future.poll(&mut context) {
+        // Clone the `Rc` out before polling so the `futures` slab isn't
+        // borrowed while the future runs (it may spawn/reregister and touch
+        // the slab itself).
+        let entry = handle.futures.borrow()[arc_self.0].clone();
+        let poll_result = PinMut::new(&mut *entry.borrow_mut()).poll(&mut context);
+        match poll_result {
             Poll::Ready(_) => {
                 debug!("future done");
-                unsafe { LeakStorage::<FutureObj<'static, ()>>::remove(arc_self.0) };
+                handle.futures.borrow_mut().remove(arc_self.0);
                 REACTOR.with(|handle| {
                     handle.report_finished();
                 });
@@ -93,19 +138,66 @@ impl Reactor {
         Ok(Reactor {
             poll: mio::Poll::new()?,
             events: RefCell::new(mio::Events::with_capacity(events_capacity)),
-            counter: RefCell::new(0),
+            counter: AtomicUsize::new(0),
+            timers: RefCell::new(BTreeMap::new()),
+            timer_ids: RefCell::new(0),
+            futures: RefCell::new(Slab::new()),
+            wakers: RefCell::new(Slab::new()),
         })
     }
 
     /// Single iteration of event loop.
     fn iterate(&self) -> Result<(), io::Error> {
+        self.iterate_inner(None)
+    }
+
+    /// Single iteration of event loop, capping the `mio::Poll` timeout at
+    /// `cap` even when there is no timer due sooner.
+    ///
+    /// Used by the threaded runtime with a zero cap, so an idle worker can
+    /// drive its own reactor without blocking and then go back to waiting on
+    /// its own run queue.
+    pub(crate) fn iterate_capped(&self, cap: Duration) -> Result<(), io::Error> {
+        self.iterate_inner(Some(cap))
+    }
+
+    fn iterate_inner(&self, cap: Option<Duration>) -> Result<(), io::Error> {
         debug!("core iteration start");
+        let timer_timeout = self.timers.borrow().keys().next().map(|(deadline, _)| {
+            let now = Instant::now();
+            if *deadline > now {
+                *deadline - now
+            } else {
+                Duration::from_secs(0)
+            }
+        });
+        let timeout = match (timer_timeout, cap) {
+            (Some(t), Some(c)) => Some(t.min(c)),
+            (Some(t), None) => Some(t),
+            (None, Some(c)) => Some(c),
+            (None, None) => None,
+        };
         let mut events = self.events.borrow_mut();
-        let _ready = self.poll.poll(&mut events, None)?;
+        let _ready = self.poll.poll(&mut events, timeout)?;
         for event in &*events {
             let mio::Token(key) = event.token();
-            let waker = unsafe { LeakStorage::<LocalWaker>::get(key) };
+            // Look the waker up rather than removing it: the slot stays owned
+            // by its source until that source's next `reregister` frees it,
+            // so a `register` during this wake can't be handed the same token.
+            let waker = self.wakers.borrow().get(key).cloned();
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+        }
+        let fired = {
+            let mut timers = self.timers.borrow_mut();
+            let now = Instant::now();
+            let remaining = timers.split_off(&(now + Duration::from_nanos(1), 0));
+            std::mem::replace(&mut *timers, remaining)
+        };
+        for (_, waker) in fired {
             waker.wake();
+            self.report_finished();
         }
         debug!("core iteration end");
         Ok(())
@@ -113,42 +205,95 @@ impl Reactor {
 
     /// Spawn the future and do event loop.
     fn start_loop(&self) -> Result<(), io::Error> {
-        while *self.counter.borrow() > 0 {
+        while self.counter.load(Ordering::SeqCst) > 0 {
             self.iterate()?;
         }
         Ok(())
     }
 
     /// Register when the handle first crated.
-    /// 
+    ///
     /// Use this function to eliminate the difference between first and other polls of future.
-    pub fn register<E>(&self, handle: &E) -> Result<(), io::Error>
+    ///
+    /// Reserves a real `wakers` slab slot (holding a no-op waker) instead of
+    /// the old `Token(0)` placeholder, so the token handed to `mio` is
+    /// already part of the collision-free token space and `reregister` has
+    /// a slot to replace rather than a magic constant to collide with.
+    pub fn register<E>(&self, handle: &E) -> Result<usize, io::Error>
     where E: mio::Evented + ?Sized {
-        // Use Token(0) to just hold the place
-        trace!("register called for {:p}", handle);
-        self.poll.register(handle, mio::Token(0), mio::Ready::empty(), mio::PollOpt::oneshot())?;
-        Ok(())
+        let key = self.wakers.borrow_mut().insert(noop_waker());
+        trace!("register called for {:p}, token: {}", handle, key);
+        self.poll.register(handle, mio::Token(key), mio::Ready::empty(), mio::PollOpt::oneshot())?;
+        Ok(key)
     }
 
     /// Manipulate waker and interest.
-    pub fn reregister<E>(&self, handle: &E, waker: LocalWaker, interest: mio::Ready) -> Result<(), io::Error>
+    ///
+    /// `previous` is the token this source was last registered with (from
+    /// `register` or an earlier `reregister`); its slot is freed before a
+    /// new one is handed out so repeated `WouldBlock`s don't leak `wakers`
+    /// slots. `iterate` only looks wakers up and never frees a slot, so
+    /// `previous` is still this source's own slot here; the removal is
+    /// best-effort all the same.
+    pub fn reregister<E>(&self, handle: &E, previous: usize, waker: LocalWaker, interest: mio::Ready) -> Result<usize, io::Error>
         where E: mio::Evented + ?Sized {
         trace!("reregister called for {:p}, interest: {:?}", handle, interest);
-        let token = mio::Token(LeakStorage::insert(waker));
-        self.poll.reregister(handle, token, interest, mio::PollOpt::oneshot())?;
-        Ok(())
+        let key = {
+            let mut wakers = self.wakers.borrow_mut();
+            wakers.try_remove(previous);
+            wakers.insert(waker)
+        };
+        self.poll.reregister(handle, mio::Token(key), interest, mio::PollOpt::oneshot())?;
+        Ok(key)
+    }
+
+    /// Register a waker to be woken once `deadline` has passed.
+    ///
+    /// Bumps the reactor counter so `start_loop` keeps running while the
+    /// timer is outstanding; returns an id used to tie-break timers sharing
+    /// the same deadline.
+    pub fn register_timer(&self, deadline: Instant, waker: LocalWaker) -> usize {
+        let id = {
+            let mut timer_ids = self.timer_ids.borrow_mut();
+            let id = *timer_ids;
+            *timer_ids += 1;
+            id
+        };
+        self.timers.borrow_mut().insert((deadline, id), waker);
+        self.report_new();
+        id
+    }
+
+    /// Cancel a timer previously armed with `register_timer`, e.g. because
+    /// the `Delay` future it backs was dropped before firing.
+    ///
+    /// No-op if the timer already fired (`iterate_inner` removes fired
+    /// timers from `timers` before calling `report_finished` for them), so
+    /// this only decrements the counter when the timer was still pending.
+    pub(crate) fn cancel_timer(&self, deadline: Instant, id: usize) {
+        if self.timers.borrow_mut().remove(&(deadline, id)).is_some() {
+            self.report_finished();
+        }
     }
 
+    /// Account for one more outstanding unit of work (a spawned future or an
+    /// armed timer).
+    ///
+    /// Also nudges the `run_threaded` executor servicing this thread, if
+    /// any, so `toyfio::spawn`/`toyfio::sleep` used from inside a threaded
+    /// task still delay that runtime's completion instead of being tracked
+    /// only by this (thread-local, per-worker) counter.
     fn report_new(&self) {
-        *self.counter.borrow_mut() += 1;
+        self.counter.fetch_add(1, Ordering::SeqCst);
+        crate::threaded::with_active_executor(|executor| executor.track_started());
     }
 
     fn report_finished(&self) {
-        if *self.counter.borrow() == 0 {
+        let previous = self.counter.fetch_sub(1, Ordering::SeqCst);
+        if previous == 0 {
             panic!("reactor counter lower than 0");
-        } else {
-            *self.counter.borrow_mut() -= 1;
         }
+        crate::threaded::with_active_executor(|executor| executor.track_finished());
     }
 
     /// The real spawn function.
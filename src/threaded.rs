@@ -0,0 +1,256 @@
+use futures::{
+    future::{Future, FutureObj},
+    task::{local_waker, Context, Spawn, SpawnObjError, Wake},
+    Poll,
+};
+use log::{debug, trace};
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    mem::PinMut,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Condvar, Mutex,
+    },
+    thread,
+    thread_local,
+    time::Duration,
+};
+
+use crate::REACTOR;
+
+thread_local! {
+    /// The executor this thread is currently servicing as a `run_threaded`
+    /// worker, if any. Lets `Reactor::report_new`/`report_finished` keep a
+    /// worker's own `toyfio::spawn`/timer bookkeeping in lock-step with the
+    /// shared outstanding-task count that `run_threaded` waits on.
+    static ACTIVE_EXECUTOR: RefCell<Option<Arc<Executor>>> = RefCell::new(None);
+}
+
+/// Run `f` with the `Executor` this thread is a worker for, if it is one.
+///
+/// Called from `Reactor::report_new`/`report_finished` so work started via
+/// the free `toyfio::spawn`/`toyfio::sleep` functions (which only know about
+/// the thread-local `REACTOR`) still counts towards the threaded runtime's
+/// completion condition.
+pub(crate) fn with_active_executor(f: impl FnOnce(&Arc<Executor>)) {
+    ACTIVE_EXECUTOR.with(|executor| {
+        if let Some(executor) = &*executor.borrow() {
+            f(executor);
+        }
+    });
+}
+
+/// A worker's private run queue.
+///
+/// Each worker also drives its own thread-local `REACTOR` (own `mio::Poll`),
+/// so a task's queue doubles as the set of tasks whose I/O sources are bound
+/// to that `Poll`.
+struct WorkerQueue {
+    tasks: Mutex<VecDeque<Arc<Task>>>,
+    not_empty: Condvar,
+}
+
+/// Shared state of a threaded runtime: one run queue per worker plus the
+/// outstanding-task counter `run_threaded` waits on.
+///
+/// There is no shared injector: `mio` binds an `Evented` source to the
+/// `mio::Poll` it was registered with, so a task can't be polled by a
+/// different worker than the one already driving its sources without that
+/// registration breaking. Each `Task` is instead assigned a home worker once
+/// (in `spawn`) and stays there for its whole lifetime.
+pub(crate) struct Executor {
+    workers: Vec<WorkerQueue>,
+    next_worker: AtomicUsize,
+    outstanding: AtomicUsize,
+}
+
+/// A spawned future plus the executor and home worker that own it.
+struct Task {
+    future: Mutex<FutureObj<'static, ()>>,
+    executor: Arc<Executor>,
+    /// Index into `Executor::workers`; fixed for the task's whole lifetime
+    /// so its I/O sources only ever see one `mio::Poll`.
+    home: usize,
+    /// Set while the task is sitting in its home worker's queue, so a wake
+    /// arriving during that window (e.g. a spurious wakeup) doesn't enqueue a
+    /// second copy. Cleared just before each poll, so a wake during the poll
+    /// (including a future waking itself) enqueues it again.
+    scheduled: AtomicBool,
+    /// Set once the future has returned `Ready`, so a late or duplicate wake
+    /// can't cause it to be polled again.
+    completed: AtomicBool,
+}
+
+/// The struct inside a threaded task's waker.
+///
+/// Unlike `InnerWaker`, waking a `TaskWaker` does not poll the future
+/// inline: it re-enqueues the task onto its home worker's queue so the poll
+/// happens on the same thread (and hence the same `mio::Poll`) every time.
+struct TaskWaker(Arc<Task>);
+
+impl Executor {
+    fn new(num_workers: usize) -> Arc<Executor> {
+        Arc::new(Executor {
+            workers: (0..num_workers)
+                .map(|_| WorkerQueue {
+                    tasks: Mutex::new(VecDeque::new()),
+                    not_empty: Condvar::new(),
+                })
+                .collect(),
+            next_worker: AtomicUsize::new(0),
+            outstanding: AtomicUsize::new(0),
+        })
+    }
+
+    fn enqueue(self: &Arc<Self>, task: Arc<Task>) {
+        let worker = &self.workers[task.home];
+        worker.tasks.lock().unwrap().push_back(task);
+        worker.not_empty.notify_one();
+    }
+
+    /// Assign `future` a home worker (round-robin) and enqueue it there.
+    fn spawn(self: &Arc<Self>, future: FutureObj<'static, ()>) {
+        self.track_started();
+        let home = self.next_worker.fetch_add(1, Ordering::SeqCst) % self.workers.len();
+        let task = Arc::new(Task {
+            future: Mutex::new(future),
+            executor: self.clone(),
+            home,
+            scheduled: AtomicBool::new(true),
+            completed: AtomicBool::new(false),
+        });
+        self.enqueue(task);
+    }
+
+    /// Account for one more outstanding unit of work: a `Task` assigned to a
+    /// worker queue by `spawn`, or a `toyfio::spawn`/timer reported via the
+    /// thread-local `REACTOR` of one of this executor's workers.
+    pub(crate) fn track_started(&self) {
+        self.outstanding.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Counterpart of `track_started`; also used once a `Task`'s future
+    /// completes.
+    pub(crate) fn track_finished(&self) {
+        if self.outstanding.fetch_sub(1, Ordering::SeqCst) == 1 {
+            // Outstanding count just reached zero: nudge every worker
+            // blocked on its queue so they notice and return.
+            for worker in &self.workers {
+                worker.not_empty.notify_all();
+            }
+        }
+    }
+
+    /// Pop a runnable task from `worker_id`'s queue, blocking on its
+    /// `not_empty` while there is none and work is still outstanding.
+    /// Returns `None` once everything is done.
+    fn next_task(self: &Arc<Self>, worker_id: usize) -> Option<Arc<Task>> {
+        let worker = &self.workers[worker_id];
+        let mut tasks = worker.tasks.lock().unwrap();
+        loop {
+            if let Some(task) = tasks.pop_front() {
+                return Some(task);
+            }
+            if self.outstanding.load(Ordering::SeqCst) == 0 {
+                return None;
+            }
+            let (guard, timeout) = worker.not_empty.wait_timeout(tasks, POLL_INTERVAL).unwrap();
+            tasks = guard;
+            if timeout.timed_out() {
+                // Give this thread's reactor a chance to fire sockets/timers
+                // registered by futures we previously polled; any of them
+                // that are ready re-enqueue themselves via `TaskWaker::wake`.
+                drop(tasks);
+                poll_own_reactor();
+                tasks = worker.tasks.lock().unwrap();
+            }
+        }
+    }
+
+    /// Worker loop: drain runnable tasks from `worker_id`'s queue,
+    /// periodically driving this thread's own `REACTOR` (and hence its own
+    /// `mio::Poll`) while idle so sockets and timers registered by futures
+    /// polled here can fire and re-enqueue themselves.
+    fn worker_loop(self: Arc<Self>, worker_id: usize) {
+        ACTIVE_EXECUTOR.with(|executor| *executor.borrow_mut() = Some(self.clone()));
+        while let Some(task) = self.next_task(worker_id) {
+            poll_task(task);
+        }
+    }
+}
+
+fn poll_own_reactor() {
+    if let Err(err) = REACTOR.with(|handle| handle.iterate_capped(Duration::from_secs(0))) {
+        debug!("threaded worker reactor iteration failed: {:?}", err);
+    }
+}
+
+/// How long an idle worker waits on its queue's `Condvar` before giving its
+/// own reactor a chance to make progress.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+impl Spawn for Arc<Executor> {
+    fn spawn_obj(&mut self, future: FutureObj<'static, ()>) -> Result<(), SpawnObjError> {
+        Executor::spawn(self, future);
+        Ok(())
+    }
+}
+
+impl Wake for TaskWaker {
+    fn wake(arc_self: &Arc<TaskWaker>) {
+        let task = &arc_self.0;
+        if task.completed.load(Ordering::SeqCst) {
+            return;
+        }
+        if task.scheduled.compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst).is_err() {
+            trace!("threaded task already scheduled, skipping duplicate wake");
+            return;
+        }
+        trace!("threaded task woken, re-enqueuing on its home worker");
+        task.executor.clone().enqueue(task.clone());
+    }
+}
+
+fn poll_task(task: Arc<Task>) {
+    trace!("polling threaded task");
+    // Reset before polling so a wake arriving during this poll (including a
+    // future waking itself) schedules a fresh run rather than being
+    // swallowed by the "already scheduled" guard in `TaskWaker::wake`.
+    task.scheduled.store(false, Ordering::SeqCst);
+    let mut executor = task.executor.clone();
+    let waker = unsafe { local_waker(Arc::new(TaskWaker(task.clone()))) };
+    let mut context = Context::new(&waker, &mut executor);
+    let poll_result = {
+        let mut future = task.future.lock().unwrap();
+        PinMut::new(&mut *future).poll(&mut context)
+    };
+    match poll_result {
+        Poll::Ready(_) => {
+            debug!("threaded future done");
+            task.completed.store(true, Ordering::SeqCst);
+            task.executor.track_finished();
+        },
+        Poll::Pending => debug!("threaded future not yet ready"),
+    }
+}
+
+/// Run `future` to completion on a multi-threaded runtime: `num_threads`
+/// worker threads each drive their own private `REACTOR` (and hence their
+/// own `mio::Poll`). A task is assigned a home worker when it is spawned and
+/// stays pinned to it for its whole lifetime — `mio` binds an `Evented`
+/// source to the `Poll` it was registered with, so a task's I/O can't
+/// migrate between workers without breaking.
+pub fn run_threaded<F: Future<Output = ()> + Send + 'static>(future: F, num_threads: usize) {
+    let executor = Executor::new(num_threads);
+    executor.spawn(FutureObj::new(Box::new(future)));
+    let workers: Vec<_> = (0..num_threads)
+        .map(|id| {
+            let executor = executor.clone();
+            thread::spawn(move || executor.worker_loop(id))
+        })
+        .collect();
+    for worker in workers {
+        worker.join().expect("threaded runtime worker panicked");
+    }
+}